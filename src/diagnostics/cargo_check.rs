@@ -0,0 +1,207 @@
+//! Runs `cargo check --message-format=json` for Rust projects and merges the
+//! resulting diagnostics into the LSP stream, catching the full-borrow-check
+//! aliasing errors (E0499/E0502/E0501 and friends) that rust-analyzer's
+//! in-process check sometimes defers.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+use serde::Deserialize;
+
+/// One top-level record from `cargo check --message-format=json`. Only
+/// `compiler-message` reasons carry a diagnostic; the rest (`build-script-executed`,
+/// `compiler-artifact`, ...) are ignored.
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompilerMessage {
+    code: Option<CompilerCode>,
+    message: String,
+    level: String,
+    spans: Vec<CompilerSpan>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompilerCode {
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompilerSpan {
+    is_primary: bool,
+    line_start: u32,
+    line_end: u32,
+    column_start: u32,
+    column_end: u32,
+    /// The span-local detail, e.g. "expected `String`, found `usize`" for an
+    /// `E0308` whose top-level `message` is just "mismatched types". Absent
+    /// for spans that don't carry extra detail.
+    label: Option<String>,
+}
+
+/// Runs `cargo check --message-format=json` in `project_dir` and returns the
+/// diagnostics it reports, converted to LSP `Diagnostic`s.
+///
+/// Only a failure to run `cargo` at all (e.g. no toolchain installed) is
+/// surfaced as an `io::Error`. A non-zero exit is not an error here, since
+/// `cargo check` exits non-zero whenever it reports any error-level
+/// diagnostic; the result is whatever JSON records stdout held, so a run
+/// that fails before compiling (e.g. not a Rust project) yields an empty list.
+pub fn collect_cargo_check_diagnostics(project_dir: &Path) -> std::io::Result<Vec<Diagnostic>> {
+    let output = Command::new("cargo")
+        .arg("check")
+        .arg("--message-format=json")
+        .current_dir(project_dir)
+        .output()?;
+
+    let diagnostics = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|msg| msg.reason == "compiler-message")
+        .filter_map(|msg| msg.message)
+        .filter_map(|message| to_lsp_diagnostic(&message))
+        .collect();
+
+    Ok(diagnostics)
+}
+
+fn to_lsp_diagnostic(message: &CompilerMessage) -> Option<Diagnostic> {
+    let span = message.spans.iter().find(|s| s.is_primary)?;
+    let range = Range::new(
+        Position::new(span.line_start.saturating_sub(1), span.column_start.saturating_sub(1)),
+        Position::new(span.line_end.saturating_sub(1), span.column_end.saturating_sub(1)),
+    );
+
+    // The primary span's `label` carries detail the top-level `message`
+    // doesn't, e.g. the "expected `X`, found `Y`" text for E0308 (whose
+    // `message` is just "mismatched types"). Fold it in so downstream
+    // enrichment/quickfix logic that scans the message for backtick-quoted
+    // identifiers and types still has something to find.
+    let message_text = match &span.label {
+        Some(label) => format!("{}: {label}", message.message),
+        None => message.message.clone(),
+    };
+
+    Some(Diagnostic {
+        range,
+        severity: Some(severity_for_level(&message.level)),
+        code: message.code.as_ref().map(|c| NumberOrString::String(c.code.clone())),
+        source: Some("cargo check".to_string()),
+        message: message_text,
+        ..Diagnostic::default()
+    })
+}
+
+fn severity_for_level(level: &str) -> DiagnosticSeverity {
+    match level {
+        "error" => DiagnosticSeverity::ERROR,
+        "warning" => DiagnosticSeverity::WARNING,
+        "note" => DiagnosticSeverity::HINT,
+        _ => DiagnosticSeverity::INFORMATION,
+    }
+}
+
+/// A diagnostic's span, used as the de-duplication key when merging the
+/// `cargo check` stream with the LSP stream: two diagnostics at the same
+/// span are treated as the same report even if their wording differs
+/// slightly between rust-analyzer and rustc.
+fn span_key(diagnostic: &Diagnostic) -> (u32, u32, u32, u32) {
+    (
+        diagnostic.range.start.line,
+        diagnostic.range.start.character,
+        diagnostic.range.end.line,
+        diagnostic.range.end.character,
+    )
+}
+
+/// Merges `lsp_diagnostics` with `cargo_diagnostics`, preferring the LSP
+/// stream's diagnostic whenever both report the same span and adding any
+/// `cargo check`-only diagnostic that the LSP stream stayed quiet on.
+pub fn merge_diagnostics(
+    lsp_diagnostics: Vec<Diagnostic>,
+    cargo_diagnostics: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    let seen: HashSet<_> = lsp_diagnostics.iter().map(span_key).collect();
+    let mut merged = lsp_diagnostics;
+    merged.extend(
+        cargo_diagnostics
+            .into_iter()
+            .filter(|d| !seen.contains(&span_key(d))),
+    );
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(is_primary: bool, line: u32, label: Option<&str>) -> CompilerSpan {
+        CompilerSpan {
+            is_primary,
+            line_start: line,
+            line_end: line,
+            column_start: 1,
+            column_end: 10,
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn folds_primary_span_label_into_message_like_user_count_fixture() {
+        // `let user_count: String = users.len();` -> rustc's top-level
+        // message is just "mismatched types"; the "expected ..." detail is
+        // only on the primary span's label.
+        let message = CompilerMessage {
+            code: Some(CompilerCode { code: "E0308".to_string() }),
+            message: "mismatched types".to_string(),
+            level: "error".to_string(),
+            spans: vec![span(true, 41, Some("expected `String`, found `usize`"))],
+        };
+
+        let diagnostic = to_lsp_diagnostic(&message).unwrap();
+
+        assert!(diagnostic.message.contains("expected `String`, found `usize`"));
+    }
+
+    #[test]
+    fn ignores_label_of_non_primary_span() {
+        let message = CompilerMessage {
+            code: None,
+            message: "unused variable".to_string(),
+            level: "warning".to_string(),
+            spans: vec![span(false, 1, Some("not primary")), span(true, 2, None)],
+        };
+
+        let diagnostic = to_lsp_diagnostic(&message).unwrap();
+
+        assert_eq!(diagnostic.message, "unused variable");
+        assert_eq!(diagnostic.range.start.line, 1);
+    }
+
+    #[test]
+    fn merge_prefers_lsp_diagnostic_and_adds_cargo_only_ones() {
+        let lsp_only = Diagnostic {
+            message: "from rust-analyzer".to_string(),
+            ..Diagnostic::default()
+        };
+        let cargo_duplicate = Diagnostic {
+            message: "from cargo check".to_string(),
+            ..Diagnostic::default()
+        };
+        let mut cargo_unique = Diagnostic::default();
+        cargo_unique.range.start.line = 5;
+        cargo_unique.message = "aliasing error cargo check alone caught".to_string();
+
+        let merged = merge_diagnostics(vec![lsp_only], vec![cargo_duplicate, cargo_unique.clone()]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].message, "from rust-analyzer");
+        assert_eq!(merged[1].message, cargo_unique.message);
+    }
+}
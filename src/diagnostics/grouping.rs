@@ -0,0 +1,196 @@
+//! Clusters cascading diagnostics into parent/child groups so a single
+//! mistake (e.g. moving a value into a map) doesn't surface as N
+//! near-duplicate follow-on errors.
+
+use std::collections::HashMap;
+
+use lsp_types::{Diagnostic, NumberOrString};
+use serde::Serialize;
+
+use super::rust_enrichment::primary_identifier;
+
+/// A root-cause diagnostic plus the downstream diagnostics it likely
+/// triggered, so a caller can emit one summarized entry per cluster instead
+/// of forwarding every follow-up error.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCluster {
+    pub root: Diagnostic,
+    pub suppressed: Vec<Diagnostic>,
+}
+
+impl DiagnosticCluster {
+    /// Number of downstream diagnostics this cluster's root explains away.
+    pub fn suppressed_count(&self) -> usize {
+        self.suppressed.len()
+    }
+}
+
+/// Error codes whose first backtick-quoted message token names the actual
+/// variable/field that moved or was borrowed — i.e. a real identifier
+/// identity, not just a type name that different unrelated errors could
+/// coincidentally share (as `E0308`'s "expected `String`, ..." does).
+const IDENTITY_BEARING_CODES: &[&str] = &["E0382", "E0507", "E0505", "E0515", "E0597"];
+
+/// How close (in source lines) a diagnostic with no identifier to cluster on
+/// must be to the most recent such diagnostic to be treated as a follow-up
+/// of it rather than an unrelated mistake. Real cascades (e.g. an unresolved
+/// name feeding straight into the next statement's type check) land on the
+/// same or next couple of lines; anything further apart is more likely a
+/// second, independent bug than a consequence of the first.
+const NON_IDENTITY_CASCADE_WINDOW: u32 = 2;
+
+/// Groups diagnostics that share a root identifier into parent/child
+/// clusters, keyed on (a) the identifier a diagnostic's message names and
+/// (b) line order: the first diagnostic to name an identifier becomes the
+/// cluster root, and every later diagnostic naming the same identifier is
+/// folded in as a suppressed follow-up, matching the "error at line N
+/// triggers errors at M>N" ordering the compiler reports.
+///
+/// Identifier matching only applies to [`IDENTITY_BEARING_CODES`] — the
+/// move/borrow codes where the quoted token really identifies the
+/// variable/field at fault. Other codes (e.g. `E0308`, where the token is an
+/// expected *type name*) are never folded into a cluster by identifier
+/// alone, since two unrelated type mismatches that happen to expect the
+/// same type are not the same root cause.
+///
+/// Diagnostics with no identifier to key on instead fall back to pure line
+/// order: one is folded into the most recent such diagnostic's cluster when
+/// it lands within [`NON_IDENTITY_CASCADE_WINDOW`] lines of it, on the
+/// assumption that a compiler error on the very next line or two was
+/// probably triggered by the one before it. Diagnostics further apart stay
+/// singletons — e.g. this crate's own reference fixture has an undefined
+/// `VERSION` constant, a missing `process_users` function, and a call to a
+/// nonexistent `display()` method scattered many lines apart; those are
+/// three independent mistakes, not one cascade, and merging them on
+/// proximity alone would wrongly suppress two of the three.
+pub fn group_cascading_diagnostics(mut diagnostics: Vec<Diagnostic>) -> Vec<DiagnosticCluster> {
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+
+    let mut clusters: Vec<DiagnosticCluster> = Vec::new();
+    let mut root_index_by_identifier: HashMap<String, usize> = HashMap::new();
+    let mut last_unkeyed_cluster: Option<(usize, u32)> = None;
+
+    for diagnostic in diagnostics {
+        let identifier = code_str(&diagnostic)
+            .filter(|code| IDENTITY_BEARING_CODES.contains(code))
+            .and_then(|_| primary_identifier(&diagnostic.message))
+            .map(str::to_string);
+
+        if let Some(identifier) = &identifier {
+            if let Some(&root_index) = root_index_by_identifier.get(identifier) {
+                clusters[root_index].suppressed.push(diagnostic);
+                continue;
+            }
+        } else if let Some((cluster_index, last_line)) = last_unkeyed_cluster {
+            if diagnostic.range.start.line.saturating_sub(last_line) <= NON_IDENTITY_CASCADE_WINDOW {
+                let line = diagnostic.range.start.line;
+                clusters[cluster_index].suppressed.push(diagnostic);
+                last_unkeyed_cluster = Some((cluster_index, line));
+                continue;
+            }
+        }
+
+        let new_index = clusters.len();
+        if let Some(identifier) = identifier {
+            root_index_by_identifier.insert(identifier, new_index);
+        } else {
+            last_unkeyed_cluster = Some((new_index, diagnostic.range.start.line));
+        }
+        clusters.push(DiagnosticCluster {
+            root: diagnostic,
+            suppressed: Vec::new(),
+        });
+    }
+
+    clusters
+}
+
+fn code_str(diagnostic: &Diagnostic) -> Option<&str> {
+    match &diagnostic.code {
+        Some(NumberOrString::String(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    fn diagnostic(code: &str, message: &str, line: u32) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Diagnostic::default()
+        }
+    }
+
+    #[test]
+    fn collapses_move_chain_like_user1_fixture() {
+        // `users.insert(user1.id, user1);` moves `user1`, and the later
+        // `user1.get_info()` is a follow-up use-of-moved-value error.
+        let root = diagnostic("E0382", "value moved here: `user1`", 10);
+        let follow_up = diagnostic("E0382", "use of moved value: `user1`", 15);
+
+        let clusters = group_cascading_diagnostics(vec![follow_up, root]);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].root.range.start.line, 10);
+        assert_eq!(clusters[0].suppressed_count(), 1);
+    }
+
+    #[test]
+    fn does_not_merge_unrelated_type_mismatches_naming_the_same_type() {
+        // Two unrelated `E0308`s that both happen to expect `String` must
+        // stay separate clusters, not collapse into one.
+        let first = diagnostic("E0308", "expected `String`, found `usize`", 5);
+        let second = diagnostic("E0308", "expected `String`, found `u32`", 20);
+
+        let clusters = group_cascading_diagnostics(vec![first, second]);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].suppressed_count(), 0);
+        assert_eq!(clusters[1].suppressed_count(), 0);
+    }
+
+    #[test]
+    fn non_identity_diagnostics_on_adjacent_lines_cluster_by_proximity() {
+        // E0425 "cannot find value `VERSION`" feeding straight into a type
+        // error on the very next line is the "error at line N triggers
+        // errors at M>N" case criterion (b) targets, even with no shared
+        // identifier to key on.
+        let root = diagnostic("E0425", "cannot find value `VERSION` in this scope", 1);
+        let follow_up = diagnostic("E0308", "mismatched types", 2);
+
+        let clusters = group_cascading_diagnostics(vec![root, follow_up]);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].root.range.start.line, 1);
+        assert_eq!(clusters[0].suppressed_count(), 1);
+    }
+
+    #[test]
+    fn diagnostics_without_a_recognized_identifier_stay_singletons_when_far_apart() {
+        // Like the reference fixture's undefined `VERSION` (line 32),
+        // missing `process_users` (line 49), and nonexistent `display()`
+        // (line 53): three unrelated mistakes, not one cascade.
+        let undefined_version = diagnostic("E0425", "cannot find value `VERSION` in this scope", 31);
+        let undefined_fn = diagnostic("E0425", "cannot find function `process_users` in this scope", 48);
+        let missing_method = diagnostic("E0599", "no method named `display` found", 52);
+
+        let clusters = group_cascading_diagnostics(vec![undefined_version, undefined_fn, missing_method]);
+
+        assert_eq!(clusters.len(), 3);
+    }
+
+    #[test]
+    fn cluster_serializes_to_json_for_sending_to_claude() {
+        let root = diagnostic("E0382", "value moved here: `user1`", 10);
+        let follow_up = diagnostic("E0382", "use of moved value: `user1`", 15);
+        let clusters = group_cascading_diagnostics(vec![follow_up, root]);
+
+        let json = serde_json::to_string(&clusters[0]).unwrap();
+        assert!(json.contains("\"suppressed\""));
+    }
+}
@@ -0,0 +1,14 @@
+//! Diagnostic post-processing: recognizing common Rust ownership/borrow
+//! errors and attaching canonical explanations before results reach Claude.
+
+pub mod cargo_check;
+pub mod grouping;
+pub mod quickfix;
+pub mod rust_enrichment;
+
+pub use cargo_check::{collect_cargo_check_diagnostics, merge_diagnostics};
+pub use grouping::{group_cascading_diagnostics, DiagnosticCluster};
+pub use quickfix::QuickFixEdit;
+pub use rust_enrichment::{
+    enrich_rust_diagnostic, enrich_rust_diagnostic_with_fixes, ErrorEnrichment, OwnershipErrorCategory,
+};
@@ -0,0 +1,282 @@
+//! Machine-applicable quick-fix edits for the Rust ownership/borrow errors
+//! `rust_enrichment` recognizes. Each edit is a byte-offset span replacement
+//! a caller can apply non-interactively, turning a diagnostic report into a
+//! fix for the most frequent beginner borrow/lifetime mistakes.
+
+use lsp_types::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+use super::rust_enrichment::{expected_and_found_types, primary_identifier, OwnershipErrorCategory};
+
+/// A single edit: replace the bytes in `[start_byte, end_byte)` of `file`
+/// with `replacement`. Serializes into the `fixes` array of a diagnostics
+/// payload so a caller can apply it non-interactively, and deserializes back
+/// out of one to actually apply it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickFixEdit {
+    pub file: String,
+    pub start_byte: u32,
+    pub end_byte: u32,
+    pub replacement: String,
+}
+
+/// Builds the quick-fix edit(s) for `diagnostic`, given the category
+/// `rust_enrichment::enrich_rust_diagnostic` assigned it and the full source
+/// text of `file` it was reported against. Byte offsets are derived by
+/// locating the diagnostic's line in `source` and finding the identifier or
+/// type name rustc's message quoted within it.
+///
+/// Returns no edits when the category has no safe mechanical fix, or when
+/// the expected identifier/type can't be located on the relevant line.
+///
+/// `UseAfterMove` (`E0382`) has no edit here: its primary span is the *use*
+/// site, after the value has already been fully moved, so inserting
+/// `.clone()` there would itself be a use of the moved value and re-trigger
+/// the same error. Only `MoveOutOfBorrow` (`E0507`/`E0505`), whose primary
+/// span is the move expression itself (e.g. `self.name`), can be fixed this
+/// way.
+pub fn fixes_for_diagnostic(
+    category: OwnershipErrorCategory,
+    diagnostic: &Diagnostic,
+    file: &str,
+    source: &str,
+) -> Vec<QuickFixEdit> {
+    match category {
+        OwnershipErrorCategory::MoveOutOfBorrow => {
+            clone_insertion_fix(diagnostic, file, source).into_iter().collect()
+        }
+        OwnershipErrorCategory::UseAfterMove => Vec::new(),
+        OwnershipErrorCategory::DanglingReference => dangling_reference_fix(diagnostic, file, source),
+        OwnershipErrorCategory::TypeMismatch => {
+            type_annotation_fix(diagnostic, file, source).into_iter().collect()
+        }
+    }
+}
+
+/// Fix for `E0507`/`E0505`: replace the moved/borrowed identifier on the
+/// diagnostic's line with `<ident>.clone()`.
+fn clone_insertion_fix(diagnostic: &Diagnostic, file: &str, source: &str) -> Option<QuickFixEdit> {
+    let ident = primary_identifier(&diagnostic.message)?;
+    let line_start = byte_offset_of_line(source, diagnostic.range.start.line);
+    let line = source.lines().nth(diagnostic.range.start.line as usize)?;
+    let (start, end) = find_in_line(line, ident)?;
+
+    Some(QuickFixEdit {
+        file: file.to_string(),
+        start_byte: line_start + start,
+        end_byte: line_start + end,
+        replacement: format!("{ident}.clone()"),
+    })
+}
+
+/// Fix for `E0515`/`E0597`: a function returns `&temp` for a value that
+/// doesn't outlive the call. Drops the leading `&` on the return expression
+/// and changes the function's `-> &str` return type to `-> String`, found by
+/// scanning backward from the diagnostic's line for the enclosing `fn`.
+fn dangling_reference_fix(diagnostic: &Diagnostic, file: &str, source: &str) -> Vec<QuickFixEdit> {
+    let return_line_start = byte_offset_of_line(source, diagnostic.range.start.line);
+    let return_line = match source.lines().nth(diagnostic.range.start.line as usize) {
+        Some(line) => line,
+        None => return Vec::new(),
+    };
+    let Some(amp_offset) = return_line.find('&') else {
+        return Vec::new();
+    };
+
+    let mut edits = vec![QuickFixEdit {
+        file: file.to_string(),
+        start_byte: return_line_start + amp_offset as u32,
+        end_byte: return_line_start + amp_offset as u32 + 1,
+        replacement: String::new(),
+    }];
+
+    let preceding_lines: Vec<(usize, &str)> = source
+        .lines()
+        .enumerate()
+        .take(diagnostic.range.start.line as usize)
+        .collect();
+
+    let sig_line_match = preceding_lines
+        .into_iter()
+        .rev()
+        // A bare closing brace at the start of a line marks the end of the
+        // previous item; don't scan past it into an unrelated function.
+        .take_while(|(_, line)| line.trim() != "}")
+        .find(|(_, line)| line.contains("-> &str"));
+
+    if let Some((sig_line_index, sig_line)) = sig_line_match {
+        let sig_line_start = byte_offset_of_line(source, sig_line_index as u32);
+        if let Some(offset) = sig_line.find("&str") {
+            edits.push(QuickFixEdit {
+                file: file.to_string(),
+                start_byte: sig_line_start + offset as u32,
+                end_byte: sig_line_start + offset as u32 + "&str".len() as u32,
+                replacement: "String".to_string(),
+            });
+        }
+    }
+
+    edits
+}
+
+/// Fix for `E0308`: replace a mismatched type annotation with the type
+/// rustc found, e.g. `let user_count: String = users.len();` gets `String`
+/// replaced with `usize`. The found type is the second backtick-quoted
+/// identifier in rustc's "expected `X`, found `Y`" message.
+fn type_annotation_fix(diagnostic: &Diagnostic, file: &str, source: &str) -> Option<QuickFixEdit> {
+    let (expected_type, found_type) = expected_and_found_types(&diagnostic.message)?;
+
+    let line_start = byte_offset_of_line(source, diagnostic.range.start.line);
+    let line = source.lines().nth(diagnostic.range.start.line as usize)?;
+    let (start, end) = find_in_line(line, expected_type)?;
+
+    Some(QuickFixEdit {
+        file: file.to_string(),
+        start_byte: line_start + start,
+        end_byte: line_start + end,
+        replacement: found_type.to_string(),
+    })
+}
+
+/// Byte offset of the start of `line` within `source`. Accounts for both
+/// `\n` and `\r\n` line endings, since `str::lines` strips either but the
+/// caller's byte offsets must match the file as it sits on disk.
+fn byte_offset_of_line(source: &str, line: u32) -> u32 {
+    let newline_width: u32 = if source.contains("\r\n") { 2 } else { 1 };
+    source
+        .lines()
+        .take(line as usize)
+        .map(|l| l.len() as u32 + newline_width)
+        .sum()
+}
+
+/// Finds `needle` as a whole word (not a substring of a larger identifier)
+/// in `line`, so e.g. searching for `x` in `let y = max(x);` matches the
+/// standalone `x` argument rather than the `x` inside `max`.
+fn find_in_line(line: &str, needle: &str) -> Option<(u32, u32)> {
+    let is_word_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let bytes = line.as_bytes();
+
+    let mut search_from = 0;
+    loop {
+        let relative_start = line[search_from..].find(needle)?;
+        let start = search_from + relative_start;
+        let end = start + needle.len();
+
+        let boundary_before = start == 0 || !is_word_byte(bytes[start - 1]);
+        let boundary_after = end == bytes.len() || !is_word_byte(bytes[end]);
+        if boundary_before && boundary_after {
+            return Some((start as u32, end as u32));
+        }
+        search_from = start + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    fn diagnostic_at(message: &str, line: u32, start_col: u32, end_col: u32) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, start_col), Position::new(line, end_col)),
+            message: message.to_string(),
+            ..Diagnostic::default()
+        }
+    }
+
+    fn edit_text<'a>(source: &'a str, edit: &QuickFixEdit) -> &'a str {
+        &source[edit.start_byte as usize..edit.end_byte as usize]
+    }
+
+    #[test]
+    fn clone_insertion_fixes_move_out_of_borrow_like_get_info_fixture() {
+        let source = "    fn get_info(&self) -> String {\n        let name = self.name;\n    }\n";
+        let diagnostic = diagnostic_at(
+            "cannot move out of `self.name` which is behind a shared reference",
+            1,
+            0,
+            0,
+        );
+
+        let fixes = fixes_for_diagnostic(OwnershipErrorCategory::MoveOutOfBorrow, &diagnostic, "main.rs", source);
+
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(edit_text(source, &fixes[0]), "self.name");
+        assert_eq!(fixes[0].replacement, "self.name.clone()");
+    }
+
+    #[test]
+    fn use_after_move_produces_no_fix() {
+        let diagnostic = diagnostic_at("use of moved value: `user1`", 0, 0, 0);
+        let fixes = fixes_for_diagnostic(
+            OwnershipErrorCategory::UseAfterMove,
+            &diagnostic,
+            "main.rs",
+            "user1.get_info();\n",
+        );
+
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn dangling_reference_fix_drops_amp_and_retypes_signature_like_get_name_ref_fixture() {
+        let source = "fn get_name_ref(&self) -> &str {\n    let temp = self.name.clone();\n    &temp\n}\n";
+        let diagnostic = diagnostic_at("`temp` does not live long enough", 2, 0, 0);
+
+        let fixes = fixes_for_diagnostic(OwnershipErrorCategory::DanglingReference, &diagnostic, "main.rs", source);
+
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(edit_text(source, &fixes[0]), "&");
+        assert_eq!(fixes[0].replacement, "");
+        assert_eq!(edit_text(source, &fixes[1]), "&str");
+        assert_eq!(fixes[1].replacement, "String");
+    }
+
+    #[test]
+    fn dangling_reference_fix_does_not_cross_into_an_earlier_unrelated_function() {
+        let source = "fn a() -> &str {\n    \"hi\"\n}\nfn b() -> i32 {\n    let v = 1;\n    &v\n}\n";
+        let diagnostic = diagnostic_at("`v` does not live long enough", 5, 0, 0);
+
+        let fixes = fixes_for_diagnostic(OwnershipErrorCategory::DanglingReference, &diagnostic, "main.rs", source);
+
+        // Only the stray `&` on `b`'s return line is dropped; `a`'s unrelated
+        // `-> &str` signature must not be rewritten.
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(edit_text(source, &fixes[0]), "&");
+    }
+
+    #[test]
+    fn type_annotation_fix_replaces_mismatched_type_like_user_count_fixture() {
+        let source = "    let user_count: String = users.len(); // Should be usize\n";
+        let diagnostic = diagnostic_at("mismatched types: expected `String`, found `usize`", 0, 0, 0);
+
+        let fixes = fixes_for_diagnostic(OwnershipErrorCategory::TypeMismatch, &diagnostic, "main.rs", source);
+
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(edit_text(source, &fixes[0]), "String");
+        assert_eq!(fixes[0].replacement, "usize");
+    }
+
+    #[test]
+    fn find_in_line_matches_whole_word_not_a_substring_of_a_larger_identifier() {
+        let line = "let y = max(x);";
+        let (start, end) = find_in_line(line, "x").unwrap();
+        assert_eq!(&line[start as usize..end as usize], "x");
+        assert_eq!(start, 12);
+    }
+
+    #[test]
+    fn quick_fix_edit_round_trips_through_json() {
+        let edit = QuickFixEdit {
+            file: "main.rs".to_string(),
+            start_byte: 10,
+            end_byte: 15,
+            replacement: "usize".to_string(),
+        };
+
+        let json = serde_json::to_string(&edit).unwrap();
+        let parsed: QuickFixEdit = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, edit);
+    }
+}
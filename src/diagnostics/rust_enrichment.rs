@@ -0,0 +1,222 @@
+//! Maps the major Rust ownership/borrow-checker error codes to a canonical
+//! explanation and a suggested fix, so raw compiler output isn't forwarded
+//! to Claude without context.
+
+use lsp_types::{Diagnostic, NumberOrString};
+use serde::Serialize;
+
+use super::quickfix::{fixes_for_diagnostic, QuickFixEdit};
+
+/// Coarse category a recognized error code falls into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OwnershipErrorCategory {
+    UseAfterMove,
+    MoveOutOfBorrow,
+    DanglingReference,
+    TypeMismatch,
+}
+
+/// Canonical explanation and suggested fix for a recognized error code,
+/// with the fix hint filled in against the diagnostic that triggered it.
+/// Serializes to the JSON payload sent to Claude alongside the raw
+/// diagnostic.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorEnrichment {
+    pub category: OwnershipErrorCategory,
+    pub explanation: String,
+    pub suggested_fix: String,
+    /// Machine-applicable edits that resolve the error, when one can be
+    /// derived mechanically. Empty when no safe fix exists (see
+    /// [`quickfix::fixes_for_diagnostic`](super::quickfix::fixes_for_diagnostic)).
+    pub fixes: Vec<QuickFixEdit>,
+}
+
+/// Static lookup table: error code -> (category, explanation, fix template).
+///
+/// `None` means the code falls outside the set of ownership/borrow errors
+/// we enrich; callers should forward the raw diagnostic unchanged.
+fn lookup_error_code(code: &str) -> Option<(OwnershipErrorCategory, &'static str, &'static str)> {
+    match code {
+        "E0382" => Some((
+            OwnershipErrorCategory::UseAfterMove,
+            "value was moved earlier and can no longer be used",
+            "clone the value before the move, or move the read ahead of the move",
+        )),
+        "E0507" | "E0505" => Some((
+            OwnershipErrorCategory::MoveOutOfBorrow,
+            "cannot move a value out of a shared or mutable reference",
+            "borrow the field instead of moving it, or clone it",
+        )),
+        "E0515" | "E0597" => Some((
+            OwnershipErrorCategory::DanglingReference,
+            "returning a reference to a value that does not outlive the call",
+            "return an owned value instead of a reference, or tie the reference to `self`",
+        )),
+        "E0308" => Some((
+            OwnershipErrorCategory::TypeMismatch,
+            "expected and found types do not match",
+            "fix the type annotation or convert the value to the expected type",
+        )),
+        _ => None,
+    }
+}
+
+/// Pulls the first backtick-quoted identifier out of a diagnostic's
+/// message, e.g. "use of moved value: `user1`" -> `user1`. rustc consistently
+/// quotes the relevant identifier this way, so this is enough to make the
+/// fix hint concrete without needing the full span text.
+pub(crate) fn primary_identifier(message: &str) -> Option<&str> {
+    let start = message.find('`')? + 1;
+    let end = message[start..].find('`')? + start;
+    Some(&message[start..end])
+}
+
+/// Extracts rustc's "expected `X`, found `Y`" type pair from an `E0308`
+/// diagnostic's message: the first two backtick-quoted tokens.
+pub(crate) fn expected_and_found_types(message: &str) -> Option<(&str, &str)> {
+    let mut backtick_items = message.split('`').skip(1).step_by(2);
+    let expected = backtick_items.next()?;
+    let found = backtick_items.next()?;
+    Some((expected, found))
+}
+
+/// Attaches a canonical explanation and a suggested fix to `diagnostic`
+/// when its code matches a recognized Rust ownership/borrow error. Carries
+/// no machine-applicable `fixes`; use [`enrich_rust_diagnostic_with_fixes`]
+/// when the source text is available.
+pub fn enrich_rust_diagnostic(diagnostic: &Diagnostic) -> Option<ErrorEnrichment> {
+    enrich(diagnostic, None)
+}
+
+/// Like [`enrich_rust_diagnostic`], but also populates `fixes` with
+/// structured, non-interactive edits derived from `file`'s `source` text.
+pub fn enrich_rust_diagnostic_with_fixes(
+    diagnostic: &Diagnostic,
+    file: &str,
+    source: &str,
+) -> Option<ErrorEnrichment> {
+    enrich(diagnostic, Some((file, source)))
+}
+
+fn enrich(diagnostic: &Diagnostic, file_and_source: Option<(&str, &str)>) -> Option<ErrorEnrichment> {
+    let code = match &diagnostic.code {
+        Some(NumberOrString::String(s)) => s.as_str(),
+        _ => return None,
+    };
+
+    let (category, default_explanation, fix_template) = lookup_error_code(code)?;
+    let ident = primary_identifier(&diagnostic.message);
+    let types = expected_and_found_types(&diagnostic.message);
+
+    let explanation = match (category, types) {
+        (OwnershipErrorCategory::TypeMismatch, Some((expected, found))) => {
+            format!("expected `{expected}`, found `{found}`")
+        }
+        _ => default_explanation.to_string(),
+    };
+
+    let suggested_fix = match (category, ident, types) {
+        (OwnershipErrorCategory::UseAfterMove, Some(ident), _) => {
+            format!("`{ident}` was moved; clone it before the move or read it before the move occurs")
+        }
+        (OwnershipErrorCategory::MoveOutOfBorrow, Some(ident), _) => {
+            format!("cannot move `{ident}` out of the reference; use `&{ident}` or `{ident}.clone()`")
+        }
+        (OwnershipErrorCategory::DanglingReference, Some(ident), _) => {
+            format!("`{ident}` does not live long enough; return an owned value instead of a reference to it")
+        }
+        (OwnershipErrorCategory::TypeMismatch, _, Some((expected, found))) => {
+            format!("change the type annotation from `{expected}` to `{found}`, or convert the value to `{expected}`")
+        }
+        _ => fix_template.to_string(),
+    };
+
+    let fixes = match file_and_source {
+        Some((file, source)) => fixes_for_diagnostic(category, diagnostic, file, source),
+        None => Vec::new(),
+    };
+
+    Some(ErrorEnrichment {
+        category,
+        explanation,
+        suggested_fix,
+        fixes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Range;
+
+    fn diagnostic(code: &str, message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::default(),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Diagnostic::default()
+        }
+    }
+
+    #[test]
+    fn enriches_use_after_move_like_users_insert_fixture() {
+        // `users.insert(user1.id, user1); ... user1.get_info();`
+        let diagnostic = diagnostic("E0382", "use of moved value: `user1`");
+        let enrichment = enrich_rust_diagnostic(&diagnostic).unwrap();
+
+        assert_eq!(enrichment.category, OwnershipErrorCategory::UseAfterMove);
+        assert!(enrichment.suggested_fix.contains("`user1`"));
+        assert!(enrichment.suggested_fix.contains("clone"));
+    }
+
+    #[test]
+    fn enriches_move_out_of_borrow_like_get_info_fixture() {
+        // `let name = self.name;` inside `fn get_info(&self)`
+        let diagnostic = diagnostic(
+            "E0507",
+            "cannot move out of `self.name` which is behind a shared reference",
+        );
+        let enrichment = enrich_rust_diagnostic(&diagnostic).unwrap();
+
+        assert_eq!(enrichment.category, OwnershipErrorCategory::MoveOutOfBorrow);
+        assert!(enrichment.suggested_fix.contains("&self.name") || enrichment.suggested_fix.contains(".clone()"));
+    }
+
+    #[test]
+    fn enriches_dangling_reference_like_get_name_ref_fixture() {
+        // `fn get_name_ref(&self) -> &str { let temp = ...; &temp }`
+        let diagnostic = diagnostic("E0515", "cannot return value referencing local variable `temp`");
+        let enrichment = enrich_rust_diagnostic(&diagnostic).unwrap();
+
+        assert_eq!(enrichment.category, OwnershipErrorCategory::DanglingReference);
+        assert!(enrichment.suggested_fix.contains("`temp`"));
+    }
+
+    #[test]
+    fn enriches_type_mismatch_with_concrete_types_like_user_count_fixture() {
+        // `let user_count: String = users.len();`
+        let diagnostic = diagnostic("E0308", "mismatched types: expected `String`, found `usize`");
+        let enrichment = enrich_rust_diagnostic(&diagnostic).unwrap();
+
+        assert_eq!(enrichment.category, OwnershipErrorCategory::TypeMismatch);
+        assert_eq!(enrichment.explanation, "expected `String`, found `usize`");
+        assert!(enrichment.suggested_fix.contains("`String`"));
+        assert!(enrichment.suggested_fix.contains("`usize`"));
+    }
+
+    #[test]
+    fn ignores_unrecognized_codes() {
+        let diagnostic = diagnostic("E9999", "some other error");
+        assert!(enrich_rust_diagnostic(&diagnostic).is_none());
+    }
+
+    #[test]
+    fn enrichment_serializes_to_json_for_sending_to_claude() {
+        let diagnostic = diagnostic("E0382", "use of moved value: `user1`");
+        let enrichment = enrich_rust_diagnostic(&diagnostic).unwrap();
+
+        let json = serde_json::to_string(&enrichment).unwrap();
+        assert!(json.contains("\"category\":\"UseAfterMove\""));
+        assert!(json.contains("`user1`"));
+    }
+}